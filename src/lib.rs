@@ -5,15 +5,27 @@
  * regardless of case, either ASCII or Unicode (with feature `unicode` enabled,
  * using [unicase](https://docs.rs/unicase)).
  *
- * Only variants of [`env::var`] and [`env::vars`] are provided, since `OsStr`s
- * are not necessarily human-readable character strings. 
+ * Variants of [`env::var`] and [`env::vars`] are provided, since `OsStr`s
+ * are not necessarily human-readable character strings, along with
+ * case-insensitive variants of [`env::set_var`] and [`env::remove_var`]
+ * that collapse any existing case-duplicate keys.
  */
 
 use std::env;
 #[cfg(feature = "unicode")]
 use unicase::UniCase;
+#[cfg(feature = "unicode")]
+use caseless::default_case_fold_str;
 
-/** Helper for uncased comparison. */
+/** Helper for uncased comparison.
+ *
+ * This is a case-insensitive string key, along the lines of Rocket's
+ * `Uncased`/`UncasedStr`: it preserves the original, case-preserved text
+ * (recoverable via [`as_str`](UncasedPartialEq::as_str)) while comparing,
+ * hashing and ordering caselessly, so it can be used directly as a
+ * `HashMap` key.
+ */
+#[derive(Debug)]
 pub struct UncasedPartialEq(
 	#[cfg(feature = "unicode")]
 	UniCase<String>,
@@ -21,6 +33,16 @@ pub struct UncasedPartialEq(
 	String,
 );
 
+impl UncasedPartialEq {
+	/** Recover the original, case-preserved string. */
+	pub fn as_str(&self) -> &str {
+		#[cfg(feature = "unicode")]
+		{ self.0.as_ref() }
+		#[cfg(not(feature = "unicode"))]
+		{ self.0.as_str() }
+	}
+}
+
 #[cfg(feature = "unicode")]
 impl<S: AsRef<str>> PartialEq<S> for UncasedPartialEq {
 	fn eq(&self, other: &S) -> bool {
@@ -35,12 +57,58 @@ impl<S: AsRef<str>> PartialEq<S> for UncasedPartialEq {
 	}
 }
 
-#[cfg(not(feature = "unicode"))]
+impl Eq for UncasedPartialEq {}
+
+impl AsRef<str> for UncasedPartialEq {
+	fn as_ref(&self) -> &str {
+		self.as_str()
+	}
+}
+
 impl std::ops::Deref for UncasedPartialEq {
-	type Target = String;
+	type Target = str;
 
 	fn deref(&self) -> &Self::Target {
-		&self.0
+		self.as_str()
+	}
+}
+
+impl std::hash::Hash for UncasedPartialEq {
+	fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+		#[cfg(feature = "unicode")]
+		self.0.hash(state);
+		#[cfg(not(feature = "unicode"))]
+		self.0.to_ascii_lowercase().hash(state);
+	}
+}
+
+impl PartialOrd for UncasedPartialEq {
+	fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for UncasedPartialEq {
+	fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+		#[cfg(feature = "unicode")]
+		{ self.0.cmp(&other.0) }
+		#[cfg(not(feature = "unicode"))]
+		{ self.0.to_ascii_lowercase().cmp(&other.0.to_ascii_lowercase()) }
+	}
+}
+
+impl From<String> for UncasedPartialEq {
+	fn from(s: String) -> Self {
+		#[cfg(feature = "unicode")]
+		{ UncasedPartialEq(UniCase::new(s)) }
+		#[cfg(not(feature = "unicode"))]
+		{ UncasedPartialEq(s) }
+	}
+}
+
+impl From<&str> for UncasedPartialEq {
+	fn from(s: &str) -> Self {
+		UncasedPartialEq::from(s.to_string())
 	}
 }
 
@@ -82,6 +150,123 @@ pub fn uncased_var<K: AsRef<str>>(key: K) -> Result<String, env::VarError> {
 	uncased_vars().find(|(k, _)| k == &key).map(|(_, v)| v).ok_or(env::VarError::NotPresent)
 }
 
+/** Helper for forced-ASCII caseless comparison.
+ *
+ * Unlike [`UncasedPartialEq`], this always uses [`str::eq_ignore_ascii_case`]
+ * regardless of whether the `unicode` feature is enabled, for callers who
+ * know their keys are ASCII and want to skip the cost of Unicode-aware
+ * comparison even when it's compiled in elsewhere in the binary.
+ */
+pub struct AsciiUncased(String);
+
+impl<S: AsRef<str>> PartialEq<S> for AsciiUncased {
+	fn eq(&self, other: &S) -> bool {
+		self.0.eq_ignore_ascii_case(other.as_ref())
+	}
+}
+
+impl std::ops::Deref for AsciiUncased {
+	type Target = str;
+
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+/** Iterator of environment variables whose keys are always compared with
+ * forced ASCII case-insensitivity, regardless of which features are
+ * compiled in.
+ */
+#[derive(Debug)]
+pub struct AsciiVars(env::Vars);
+
+impl Iterator for AsciiVars {
+	type Item = (AsciiUncased, String);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		self.0.next().map(|(k, v)| (AsciiUncased(k), v))
+	}
+}
+
+/** Get an [`AsciiVars`] whose keys are always compared with forced ASCII
+ * case-insensitivity, regardless of which features are compiled in.
+ */
+pub fn ascii_vars() -> AsciiVars {
+	AsciiVars(env::vars())
+}
+
+/** Get value by a key like [`env::var`], but accept a key of any case of
+ * `AsRef<str>` rather than `AsRef<OsStr>`, always compared using forced
+ * ASCII case-insensitivity regardless of which features are compiled in.
+ */
+pub fn ascii_var<K: AsRef<str>>(key: K) -> Result<String, env::VarError> {
+	let key = key.as_ref();
+	ascii_vars().find(|(k, _)| k == &key).map(|(_, v)| v).ok_or(env::VarError::NotPresent)
+}
+
+/** Fold a key for caseless matching.
+ *
+ * With feature `unicode` enabled, this applies full Unicode case folding
+ * (as opposed to case *mapping*, which is what [`str::to_lowercase`] and
+ * [`str::to_uppercase`] perform), so that e.g. `ß` and `SS` compare equal.
+ * Without it, the key is ASCII-lowercased, which is exact case folding for
+ * the ASCII subset.
+ */
+fn fold(key: &str) -> String {
+	#[cfg(feature = "unicode")]
+	{ default_case_fold_str(key) }
+	#[cfg(not(feature = "unicode"))]
+	{ key.to_ascii_lowercase() }
+}
+
+/** Lowercase a key the same way [`lower_vars`] does. */
+fn fold_lower(key: &str) -> String {
+	#[cfg(feature = "unicode")]
+	{ key.to_lowercase() }
+	#[cfg(not(feature = "unicode"))]
+	{ key.to_ascii_lowercase() }
+}
+
+/** UPPERCASE a key the same way [`upper_vars`] does. */
+fn fold_upper(key: &str) -> String {
+	#[cfg(feature = "unicode")]
+	{ key.to_uppercase() }
+	#[cfg(not(feature = "unicode"))]
+	{ key.to_ascii_uppercase() }
+}
+
+/** Iterator of environment variables whose keys are folded for caseless
+ * comparison: full Unicode case folding with feature `unicode` enabled,
+ * ASCII lowercasing without it.
+ */
+#[derive(Debug)]
+pub struct FoldedVars(env::Vars);
+
+impl Iterator for FoldedVars {
+	type Item = (String, String);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		self.0.next().map(|(k, v)| (fold(&k), v))
+	}
+}
+
+/** Get an iterator of environment variables like [`env::vars`], but with
+ * keys folded for caseless comparison.
+ */
+pub fn folded_vars() -> FoldedVars {
+	FoldedVars(env::vars())
+}
+
+/** Get value by a key like [`env::var`], but accept a key of any case of
+ * `AsRef<str>` rather than `AsRef<OsStr>`, compared using case folding
+ * rather than case mapping: full Unicode case folding with feature `unicode`
+ * enabled, ASCII lowercasing without it.
+ */
+pub fn folded_var<K: AsRef<str>>(key: K) -> Result<String, env::VarError> {
+	let key = fold(key.as_ref());
+	folded_vars().find(|(k, _)| k == &key).map(|(_, v)| v).ok_or(env::VarError::NotPresent)
+}
+
 /** Iterator of environment variables whose keys are lowercased.
  */
 #[derive(Debug)]
@@ -148,6 +333,137 @@ pub fn upper_var<K: AsRef<str>>(key: K) -> Result<String, env::VarError> {
 	upper_vars().find(|(k, _)| k == key).map(|(_, v)| v).ok_or(env::VarError::NotPresent)
 }
 
+/** Compare two keys the same way [`uncased_var`] does. */
+#[cfg(feature = "unicode")]
+fn uncased_eq(a: &str, b: &str) -> bool {
+	UniCase::new(a) == UniCase::new(b)
+}
+
+#[cfg(not(feature = "unicode"))]
+fn uncased_eq(a: &str, b: &str) -> bool {
+	a.eq_ignore_ascii_case(b)
+}
+
+/** Remove every environment variable whose key matches `key`
+ * case-insensitively, like [`env::remove_var`] but collapsing any
+ * duplicate-by-case entries (e.g. both `Path` and `PATH`) rather than
+ * leaving them in place.
+ */
+pub fn uncased_remove_var<K: AsRef<str>>(key: K) {
+	let key = key.as_ref();
+	for (k, _) in env::vars() {
+		if uncased_eq(&k, key) {
+			env::remove_var(k);
+		}
+	}
+}
+
+/** Set an environment variable like [`env::set_var`], but first remove any
+ * existing key that matches case-insensitively, so the process ends up
+ * with a single entry for `key` regardless of what case it was previously
+ * set in.
+ */
+pub fn uncased_set_var<K: AsRef<str>, V: AsRef<str>>(key: K, value: V) {
+	let key = key.as_ref();
+	uncased_remove_var(key);
+	env::set_var(key, value.as_ref());
+}
+
+/** Remove every environment variable whose key matches `key`
+ * case-insensitively, like [`uncased_remove_var`].
+ */
+pub fn lower_remove_var<K: AsRef<str>>(key: K) {
+	uncased_remove_var(key)
+}
+
+/** Set an environment variable under its lowercased key, like
+ * [`uncased_set_var`] but normalizing the stored key to lower case.
+ */
+pub fn lower_set_var<K: AsRef<str>, V: AsRef<str>>(key: K, value: V) {
+	let key = key.as_ref();
+	#[cfg(feature = "unicode")]
+	let key = key.to_lowercase();
+	#[cfg(not(feature = "unicode"))]
+	let key = key.to_ascii_lowercase();
+	uncased_remove_var(&key);
+	env::set_var(key, value.as_ref());
+}
+
+/** Remove every environment variable whose key matches `key`
+ * case-insensitively, like [`uncased_remove_var`]. */
+pub fn upper_remove_var<K: AsRef<str>>(key: K) {
+	uncased_remove_var(key)
+}
+
+/** Set an environment variable under its UPPERCASED key, like
+ * [`uncased_set_var`] but normalizing the stored key to UPPER case.
+ */
+pub fn upper_set_var<K: AsRef<str>, V: AsRef<str>>(key: K, value: V) {
+	let key = key.as_ref();
+	#[cfg(feature = "unicode")]
+	let key = key.to_uppercase();
+	#[cfg(not(feature = "unicode"))]
+	let key = key.to_ascii_uppercase();
+	uncased_remove_var(&key);
+	env::set_var(key, value.as_ref());
+}
+
+#[derive(Debug)]
+enum SnapshotInner {
+	Uncased(std::collections::HashMap<UncasedPartialEq, String>),
+	Lower(std::collections::HashMap<String, String>),
+	Upper(std::collections::HashMap<String, String>),
+}
+
+/** A one-time, indexed snapshot of the environment for repeated
+ * case-insensitive lookups.
+ *
+ * [`uncased_var`], [`lower_var`] and [`upper_var`] each call [`env::vars`]
+ * and linearly scan the whole environment, which is O(n) per lookup. A
+ * `Snapshot` instead reads the environment once and indexes it into a map
+ * keyed by the chosen normalized form, turning N lookups into one O(n)
+ * build plus O(1) per query.
+ */
+#[derive(Debug)]
+pub struct Snapshot(SnapshotInner);
+
+impl Snapshot {
+	/** Build a snapshot indexed like [`uncased_vars`]. */
+	pub fn uncased() -> Self {
+		Snapshot(SnapshotInner::Uncased(uncased_vars().collect()))
+	}
+
+	/** Build a snapshot indexed like [`lower_vars`]. */
+	pub fn lower() -> Self {
+		Snapshot(SnapshotInner::Lower(lower_vars().collect()))
+	}
+
+	/** Build a snapshot indexed like [`upper_vars`]. */
+	pub fn upper() -> Self {
+		Snapshot(SnapshotInner::Upper(upper_vars().collect()))
+	}
+
+	/** Get a value by key, normalizing the query the same way the snapshot was built. */
+	pub fn get<K: AsRef<str>>(&self, key: K) -> Option<&str> {
+		let key = key.as_ref();
+		match &self.0 {
+			SnapshotInner::Uncased(map) => map.get(&UncasedPartialEq::from(key)).map(String::as_str),
+			SnapshotInner::Lower(map) => map.get(&fold_lower(key)).map(String::as_str),
+			SnapshotInner::Upper(map) => map.get(&fold_upper(key)).map(String::as_str),
+		}
+	}
+
+	/** Iterate over the snapshot's entries as `(key, value)` pairs. */
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+		let iter: Box<dyn Iterator<Item = (&str, &str)>> = match &self.0 {
+			SnapshotInner::Uncased(map) => Box::new(map.iter().map(|(k, v)| (k.as_str(), v.as_str()))),
+			SnapshotInner::Lower(map) => Box::new(map.iter().map(|(k, v)| (k.as_str(), v.as_str()))),
+			SnapshotInner::Upper(map) => Box::new(map.iter().map(|(k, v)| (k.as_str(), v.as_str()))),
+		};
+		iter
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -178,5 +494,75 @@ mod tests {
 		assert_eq!(lower_var("maße"), Ok("42".to_string()));
 		assert_eq!(upper_var("MASSE"), Ok("42".to_string()));
 	}
+
+	#[cfg(feature = "unicode")]
+	#[test]
+	fn folded() {
+		env::set_var("Straße", "42");
+		assert_eq!(folded_var("STRASSE"), Ok("42".to_string()));
+	}
+
+	#[test]
+	fn uncased_set_collapses_duplicates() {
+		env::set_var("Dupe", "old");
+		env::set_var("DUPE", "old");
+		uncased_set_var("dupe", "new");
+		assert_eq!(env::vars().filter(|(k, _)| k.eq_ignore_ascii_case("dupe")).count(), 1);
+		assert_eq!(uncased_var("Dupe"), Ok("new".to_string()));
+	}
+
+	#[test]
+	fn lower_set_normalizes_key() {
+		env::set_var("Shout", "old");
+		lower_set_var("SHOUT", "new");
+		assert_eq!(env::var("shout"), Ok("new".to_string()));
+		assert_eq!(env::var("Shout"), Err(env::VarError::NotPresent));
+	}
+
+	#[test]
+	fn uncased_key_in_hashmap() {
+		env::set_var("HashKey", "world");
+		let map: std::collections::HashMap<_, _> = uncased_vars().collect();
+		let value = map.get(&UncasedPartialEq::from("hashkey")).expect("present");
+		assert_eq!(value, "world");
+	}
+
+	#[test]
+	fn snapshot_lower() {
+		env::set_var("SNAP", "value");
+		let snapshot = Snapshot::lower();
+		assert_eq!(snapshot.get("snap"), Some("value"));
+		assert_eq!(snapshot.get("SNAP"), Some("value"));
+		assert_eq!(snapshot.get("nonexistent-snap-key"), None);
+	}
+
+	#[test]
+	fn snapshot_upper() {
+		env::set_var("shout_snap", "value");
+		let snapshot = Snapshot::upper();
+		assert_eq!(snapshot.get("SHOUT_SNAP"), Some("value"));
+		assert_eq!(snapshot.get("shout_snap"), Some("value"));
+	}
+
+	#[test]
+	fn snapshot_uncased() {
+		env::set_var("UnCasedSnap", "value");
+		let snapshot = Snapshot::uncased();
+		assert_eq!(snapshot.get("uncasedsnap"), Some("value"));
+		assert_eq!(snapshot.get("UNCASEDSNAP"), Some("value"));
+	}
+
+	#[test]
+	fn snapshot_iter() {
+		env::set_var("ITER_SNAP", "value");
+		let snapshot = Snapshot::lower();
+		assert!(snapshot.iter().any(|(k, v)| k == "iter_snap" && v == "value"));
+	}
+
+	#[test]
+	fn ascii() {
+		env::set_var("ASCII_HELLO", "world");
+		assert_eq!(ascii_var("ascii_hello"), Ok("world".to_string()));
+	}
 }
 